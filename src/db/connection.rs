@@ -1,11 +1,17 @@
 use std::env;
+use std::future::Future;
 use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
 
 use libsql::{Builder, Database, params};
-use sqlx::sqlite::SqlitePool;
+use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePool, SqlitePoolOptions};
 use tracing::{debug, error, info};
 
 use crate::db::error::{DbError, DbResult};
+use crate::db::files::{self, FileRecord};
 
 #[allow(dead_code)]
 /// Database configuration
@@ -17,6 +23,14 @@ pub struct DbConfig {
     pub auth_token: Option<String>,
     /// Replica configuration (for embedded replication)
     pub replica: Option<ReplicaConfig>,
+    /// Create the local SQLite file if it doesn't exist yet
+    pub create_if_missing: bool,
+    /// Maximum number of pooled connections
+    pub max_connections: u32,
+    /// How long a connection waits on a locked database before giving up
+    pub busy_timeout: Duration,
+    /// SQLite journal mode used for the local pool
+    pub journal_mode: SqliteJournalMode,
 }
 
 /// Configuration for embedded replicas
@@ -74,10 +88,35 @@ impl DbConfig {
             None
         };
 
+        let create_if_missing = env::var("DATABASE_CREATE_IF_MISSING")
+            .ok()
+            .map(|v| v != "false")
+            .unwrap_or(true);
+
+        let max_connections = env::var("DATABASE_MAX_CONNECTIONS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(10);
+
+        let busy_timeout = env::var("DATABASE_BUSY_TIMEOUT_MS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .map(Duration::from_millis)
+            .unwrap_or_else(|| Duration::from_secs(5));
+
+        let journal_mode = env::var("DATABASE_JOURNAL_MODE")
+            .ok()
+            .and_then(|v| parse_journal_mode(&v))
+            .unwrap_or(SqliteJournalMode::Wal);
+
         Ok(DbConfig {
             url,
             auth_token,
             replica,
+            create_if_missing,
+            max_connections,
+            busy_timeout,
+            journal_mode,
         })
     }
 
@@ -95,43 +134,156 @@ impl DbConfig {
     }
 }
 
+/// Parse a `DATABASE_JOURNAL_MODE` value (case-insensitive) into a `SqliteJournalMode`
+fn parse_journal_mode(value: &str) -> Option<SqliteJournalMode> {
+    match value.to_ascii_lowercase().as_str() {
+        "delete" => Some(SqliteJournalMode::Delete),
+        "truncate" => Some(SqliteJournalMode::Truncate),
+        "persist" => Some(SqliteJournalMode::Persist),
+        "memory" => Some(SqliteJournalMode::Memory),
+        "wal" => Some(SqliteJournalMode::Wal),
+        "off" => Some(SqliteJournalMode::Off),
+        _ => {
+            debug!("Unrecognized DATABASE_JOURNAL_MODE '{}', ignoring", value);
+            None
+        }
+    }
+}
+
+/// The storage backend a `DbPool` talks to. Handlers go through `DbExecutor` rather
+/// than matching on this directly, so they don't care which variant is live.
+#[derive(Clone)]
+pub enum DbBackend {
+    /// A plain local SQLite file, accessed through sqlx
+    Local(SqlitePool),
+    /// A remote Turso/libsql database, accessed directly over the network
+    Remote(libsql::Connection),
+    /// A local embedded replica kept in sync with a remote primary
+    Replica(Arc<ReplicaHandle>),
+}
+
 /// Database connection pool
-pub type DbPool = SqlitePool;
+pub type DbPool = DbBackend;
 
-/// Create a new database connection pool
-pub async fn create_pool() -> DbResult<DbPool> {
-    let config = DbConfig::from_env()?;
+/// Operations handlers need, implemented for every storage backend so callers don't
+/// have to match on which one is live.
+pub trait DbExecutor {
+    /// Run a DDL/DML statement with no result rows
+    fn execute(&self, sql: &str) -> impl Future<Output = DbResult<()>> + Send;
+
+    /// Look up a file by path
+    fn fetch_file_by_path(&self, path: &str) -> impl Future<Output = DbResult<Option<FileRecord>>> + Send;
+
+    /// Confirm the backend is reachable
+    fn health_check(&self) -> impl Future<Output = DbResult<()>> + Send;
+
+    /// Create or overwrite a file. Routed to the primary for `Replica` backends.
+    fn upsert_file<'a>(&'a self, path: &'a str, bytes: &'a [u8]) -> impl Future<Output = DbResult<()>> + Send + 'a;
+
+    /// Delete a file by path, returning whether a row was removed. Routed to the
+    /// primary for `Replica` backends.
+    fn delete_file<'a>(&'a self, path: &'a str) -> impl Future<Output = DbResult<bool>> + Send + 'a;
+}
+
+impl DbExecutor for DbBackend {
+    async fn execute(&self, sql: &str) -> DbResult<()> {
+        match self {
+            DbBackend::Local(pool) => execute_query(pool, sql).await,
+            // `execute_batch` (rather than `execute`) so callers like migrations can
+            // pass multi-statement SQL, same as sqlx does for the local backend.
+            DbBackend::Remote(conn) => {
+                conn.execute_batch(sql)
+                    .await
+                    .map_err(|e| DbError::Query(format!("Query execution error: {}", e)))?;
+                Ok(())
+            }
+            DbBackend::Replica(replica) => {
+                let conn = replica.connect()?;
+                conn.execute_batch(sql)
+                    .await
+                    .map_err(|e| DbError::Query(format!("Query execution error: {}", e)))?;
+                Ok(())
+            }
+        }
+    }
+
+    async fn fetch_file_by_path(&self, path: &str) -> DbResult<Option<FileRecord>> {
+        match self {
+            DbBackend::Local(pool) => files::get_by_path(pool, path).await,
+            DbBackend::Remote(conn) => files::get_by_path_libsql(conn, path).await,
+            DbBackend::Replica(replica) => files::get_by_path_replica(replica, path).await,
+        }
+    }
+
+    async fn health_check(&self) -> DbResult<()> {
+        match self {
+            DbBackend::Local(pool) => check_connection(pool).await,
+            DbBackend::Remote(conn) => {
+                conn.execute("SELECT 1", params![])
+                    .await
+                    .map_err(|e| DbError::Connection(format!("Failed to execute test query: {}", e)))?;
+                Ok(())
+            }
+            DbBackend::Replica(replica) => check_connection_replica(replica).await,
+        }
+    }
+
+    async fn upsert_file(&self, path: &str, bytes: &[u8]) -> DbResult<()> {
+        match self {
+            DbBackend::Local(pool) => files::upsert(pool, path, bytes).await,
+            DbBackend::Remote(conn) => files::upsert_libsql(conn, path, bytes).await,
+            DbBackend::Replica(replica) => files::upsert_libsql(replica.primary(), path, bytes).await,
+        }
+    }
 
-    info!("Initializing database connection");
+    async fn delete_file(&self, path: &str) -> DbResult<bool> {
+        match self {
+            DbBackend::Local(pool) => files::delete(pool, path).await,
+            DbBackend::Remote(conn) => files::delete_libsql(conn, path).await,
+            DbBackend::Replica(replica) => files::delete_libsql(replica.primary(), path).await,
+        }
+    }
+}
+
+/// Create a new local SQLite connection pool, tuned from `DbConfig`
+async fn create_local_pool(config: &DbConfig) -> DbResult<SqlitePool> {
+    info!("Initializing local database connection");
     debug!("Database config: {:?}", config);
 
-    let is_local = config.is_local();
-    debug!("Database is local: {}", is_local);
+    let connect_options = SqliteConnectOptions::from_str(&config.url)
+        .map_err(|e| DbError::Connection(format!("Invalid database URL: {}", e)))?
+        .create_if_missing(config.create_if_missing)
+        .busy_timeout(config.busy_timeout)
+        .journal_mode(config.journal_mode);
 
-    let pool = SqlitePool::connect(&config.url)
+    let pool = SqlitePoolOptions::new()
+        .max_connections(config.max_connections)
+        .connect_with(connect_options)
         .await
         .map_err(|e| DbError::Connection(format!("Failed to create pool: {}", e)))?;
 
-    // Example usage of execute_query during initialization
-    execute_query(
-        &pool,
-        "CREATE TABLE IF NOT EXISTS example (id INTEGER PRIMARY KEY, value TEXT);",
-    )
-    .await?;
-
-    // Example usage of execute_parameterized_query during initialization
-    execute_parameterized_query(
-        &pool,
-        "INSERT INTO example (id, value) VALUES (?, ?);",
-        (1, "example_value"),
-    )
-    .await?;
-
     info!("Database connection pool created successfully");
 
     Ok(pool)
 }
 
+/// Connect directly to a remote Turso/libsql database
+async fn create_remote_connection(config: &DbConfig) -> DbResult<libsql::Connection> {
+    info!("Initializing remote database connection to {}", config.url);
+    connect_remote(&config.url, config.auth_token.as_deref()).await
+}
+
+/// Open a connection to a remote libsql/Turso URL
+async fn connect_remote(url: &str, auth_token: Option<&str>) -> DbResult<libsql::Connection> {
+    let db = Builder::new_remote(url.to_string(), auth_token.unwrap_or_default().to_string())
+        .build()
+        .await
+        .map_err(|e| DbError::Connection(format!("Failed to build remote database: {}", e)))?;
+
+    db.connect()
+        .map_err(|e| DbError::Connection(format!("Failed to connect to remote database: {}", e)))
+}
+
 /// Set up an embedded replica database that syncs with a Turso cloud database
 async fn setup_embedded_replica(config: &DbConfig) -> DbResult<Database> {
     let replica_config = config.replica.as_ref().ok_or_else(|| {
@@ -187,8 +339,67 @@ async fn setup_embedded_replica(config: &DbConfig) -> DbResult<Database> {
     Ok(db)
 }
 
+/// A handle to a running embedded replica: the synced libsql `Database`, a direct
+/// connection to the primary for writes, and bookkeeping for the background sync
+/// loop and the `/admin/sync` route.
+pub struct ReplicaHandle {
+    db: Database,
+    primary: libsql::Connection,
+    last_synced_frame: AtomicU64,
+}
+
+impl ReplicaHandle {
+    fn new(db: Database, primary: libsql::Connection) -> Self {
+        Self {
+            db,
+            primary,
+            last_synced_frame: AtomicU64::new(0),
+        }
+    }
+
+    /// Open a connection against the replica, for serving reads
+    pub fn connect(&self) -> DbResult<libsql::Connection> {
+        self.db
+            .connect()
+            .map_err(|e| DbError::Connection(format!("Failed to connect to replica: {}", e)))
+    }
+
+    /// A connection to the primary, for writes
+    pub fn primary(&self) -> &libsql::Connection {
+        &self.primary
+    }
+
+    /// Pull the latest changes from the primary and record the frame number the replica reached
+    pub async fn sync(&self) -> DbResult<u64> {
+        let result = self
+            .db
+            .sync()
+            .await
+            .map_err(|e| DbError::Connection(format!("Replica sync failed: {}", e)))?;
+
+        let frame_no = result.frame_no().unwrap_or(0) as u64;
+        self.last_synced_frame.store(frame_no, Ordering::Relaxed);
+        Ok(frame_no)
+    }
+
+    /// The frame number as of the last successful sync
+    pub fn last_synced_frame(&self) -> u64 {
+        self.last_synced_frame.load(Ordering::Relaxed)
+    }
+}
+
+/// How often the background task should sync the embedded replica with the primary
+pub fn replica_sync_interval() -> Duration {
+    let secs = env::var("REPLICA_SYNC_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(30);
+
+    Duration::from_secs(secs)
+}
+
 /// Check database connection health
-pub async fn check_connection(pool: &DbPool) -> DbResult<()> {
+pub async fn check_connection(pool: &SqlitePool) -> DbResult<()> {
     sqlx::query("SELECT 1")
         .execute(pool)
         .await
@@ -197,8 +408,19 @@ pub async fn check_connection(pool: &DbPool) -> DbResult<()> {
     Ok(())
 }
 
+/// Check the embedded replica's connection health
+pub async fn check_connection_replica(replica: &ReplicaHandle) -> DbResult<()> {
+    let conn = replica.connect()?;
+
+    conn.execute("SELECT 1", params![])
+        .await
+        .map_err(|e| DbError::Connection(format!("Failed to execute test query on replica: {}", e)))?;
+
+    Ok(())
+}
+
 /// Execute a single SQL query and return the rows
-pub async fn execute_query(pool: &DbPool, query: &str) -> DbResult<()> {
+pub async fn execute_query(pool: &SqlitePool, query: &str) -> DbResult<()> {
     sqlx::query(query)
         .execute(pool)
         .await
@@ -207,9 +429,12 @@ pub async fn execute_query(pool: &DbPool, query: &str) -> DbResult<()> {
     Ok(())
 }
 
-/// Execute a parameterized SQL query
+/// Execute a parameterized SQL query. Currently only exercised by tests - kept
+/// around as the general-purpose counterpart to `execute_query` for callers that
+/// need bound parameters.
+#[allow(dead_code)]
 pub async fn execute_parameterized_query<'a>(
-    pool: &DbPool,
+    pool: &SqlitePool,
     query: &'a str,
     params: (
         impl sqlx::Encode<'a, sqlx::Sqlite> + sqlx::Type<sqlx::Sqlite> + Send + 'a,
@@ -226,23 +451,23 @@ pub async fn execute_parameterized_query<'a>(
     Ok(())
 }
 
-/// Initialize the database, including setting up an embedded replica if configured
+/// Initialize the database, dispatching to the right backend based on `DbConfig`:
+/// an embedded replica when `USE_REPLICA=true`, a remote libsql connection for
+/// `libsql://`/`http(s)://` URLs, or a local SQLite pool otherwise.
 pub async fn initialize_database() -> DbResult<DbPool> {
     let config = DbConfig::from_env()?;
 
     if config.is_replica() {
         info!("Setting up embedded replica");
-        let _replica_db = setup_embedded_replica(&config).await?;
-
-        // Use the replica's local path for sqlx connection
-        let pool = SqlitePool::connect(&config.replica.as_ref().unwrap().local_path)
-            .await
-            .map_err(|e| {
-                DbError::Connection(format!("Failed to create pool for replica: {}", e))
-            })?;
+        let replica_config = config.replica.as_ref().expect("is_replica implies replica config");
+        let replica_db = setup_embedded_replica(&config).await?;
+        let primary = connect_remote(&replica_config.primary_url, Some(&replica_config.auth_token)).await?;
+        return Ok(DbBackend::Replica(Arc::new(ReplicaHandle::new(replica_db, primary))));
+    }
 
-        return Ok(pool);
+    if config.is_local() {
+        return Ok(DbBackend::Local(create_local_pool(&config).await?));
     }
 
-    create_pool().await
+    Ok(DbBackend::Remote(create_remote_connection(&config).await?))
 }
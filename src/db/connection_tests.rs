@@ -1,30 +1,41 @@
-use crate::db::connection::{DbPool, execute_parameterized_query, execute_query};
+use crate::db::connection::{DbBackend, execute_parameterized_query, execute_query};
+use crate::db::spawn_test_db;
 
 #[tokio::test]
 async fn test_execute_query() {
-    let pool = DbPool::connect("sqlite::memory:").await.unwrap();
-    sqlx::query("CREATE TABLE test (id INTEGER PRIMARY KEY, value TEXT);")
-        .execute(&pool)
-        .await
-        .unwrap();
+    let (pool, _guard) = spawn_test_db().await;
+    let DbBackend::Local(sqlite_pool) = &pool else {
+        panic!("spawn_test_db always returns a Local backend");
+    };
 
-    execute_query(&pool, "INSERT INTO test (value) VALUES ('test_value');")
-        .await
-        .unwrap();
+    execute_query(
+        sqlite_pool,
+        "INSERT INTO files (id, path, content, content_type, size, last_modified, created_at) \
+         VALUES ('test-id', 'test.txt', X'74657374', 'text/plain', 4, 0, 0);",
+    )
+    .await
+    .unwrap();
 }
 
 #[tokio::test]
 async fn test_execute_parameterized_query() {
-    let pool = DbPool::connect("sqlite::memory:").await.unwrap();
-    sqlx::query("CREATE TABLE test (id INTEGER PRIMARY KEY, value TEXT);")
-        .execute(&pool)
-        .await
-        .unwrap();
+    let (pool, _guard) = spawn_test_db().await;
+    let DbBackend::Local(sqlite_pool) = &pool else {
+        panic!("spawn_test_db always returns a Local backend");
+    };
+
+    execute_query(
+        sqlite_pool,
+        "INSERT INTO files (id, path, content, content_type, size, last_modified, created_at) \
+         VALUES ('test-id', 'test.txt', X'74657374', 'text/plain', 4, 0, 0);",
+    )
+    .await
+    .unwrap();
 
     execute_parameterized_query(
-        &pool,
-        "INSERT INTO test (id, value) VALUES (?, ?);",
-        (1, "test_value"),
+        sqlite_pool,
+        "UPDATE files SET content_type = ? WHERE path = ?;",
+        ("text/markdown", "test.txt"),
     )
     .await
     .unwrap();
@@ -13,7 +13,6 @@ pub enum DbError {
     Configuration(String),
 
     #[error("Database initialization error: {0}")]
-    #[allow(dead_code)]
     Initialization(String),
 }
 
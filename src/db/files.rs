@@ -0,0 +1,246 @@
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use libsql::params;
+use sqlx::Row;
+use sqlx::sqlite::SqlitePool;
+use tracing::debug;
+use uuid::Uuid;
+
+use crate::db::connection::ReplicaHandle;
+use crate::db::error::{DbError, DbResult};
+
+/// A file record stored in the `files` table
+#[derive(Debug, Clone)]
+pub struct FileRecord {
+    pub content: Vec<u8>,
+    pub content_type: String,
+    pub size: i64,
+    pub last_modified: i64,
+}
+
+/// Look up a file by its path against a local SQLite pool
+pub async fn get_by_path(pool: &SqlitePool, path: &str) -> DbResult<Option<FileRecord>> {
+    debug!("Looking up file by path: {}", path);
+
+    let row = sqlx::query(
+        "SELECT content, content_type, size, last_modified FROM files WHERE path = ?",
+    )
+    .bind(path)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| DbError::Query(format!("Failed to look up file by path: {}", e)))?;
+
+    Ok(row.map(|row| FileRecord {
+        content: row.get("content"),
+        content_type: row.get("content_type"),
+        size: row.get("size"),
+        last_modified: row.get("last_modified"),
+    }))
+}
+
+/// Look up a file by its path against an embedded replica
+pub async fn get_by_path_replica(replica: &ReplicaHandle, path: &str) -> DbResult<Option<FileRecord>> {
+    debug!("Looking up file by path on replica: {}", path);
+
+    let conn = replica.connect()?;
+    get_by_path_libsql(&conn, path).await
+}
+
+/// Look up a file by its path over a raw libsql connection (remote or replica)
+pub async fn get_by_path_libsql(conn: &libsql::Connection, path: &str) -> DbResult<Option<FileRecord>> {
+    let mut rows = conn
+        .query(
+            "SELECT content, content_type, size, last_modified FROM files WHERE path = ?1",
+            params![path],
+        )
+        .await
+        .map_err(|e| DbError::Query(format!("Failed to look up file by path: {}", e)))?;
+
+    let row = rows
+        .next()
+        .await
+        .map_err(|e| DbError::Query(format!("Failed to read row: {}", e)))?;
+
+    let Some(row) = row else {
+        return Ok(None);
+    };
+
+    Ok(Some(FileRecord {
+        content: row
+            .get::<Vec<u8>>(0)
+            .map_err(|e| DbError::Query(format!("Failed to read content column: {}", e)))?,
+        content_type: row
+            .get::<String>(1)
+            .map_err(|e| DbError::Query(format!("Failed to read content_type column: {}", e)))?,
+        size: row
+            .get::<i64>(2)
+            .map_err(|e| DbError::Query(format!("Failed to read size column: {}", e)))?,
+        last_modified: row
+            .get::<i64>(3)
+            .map_err(|e| DbError::Query(format!("Failed to read last_modified column: {}", e)))?,
+    }))
+}
+
+/// The derived fields for an upsert: a fresh id (used only if this is a first
+/// insert - an overwrite keeps the existing row's id), sniffed content type, size,
+/// and the current time.
+struct UpsertFields {
+    id: String,
+    content_type: String,
+    size: i64,
+    now: i64,
+}
+
+/// Compute the fields shared by `upsert` and `upsert_libsql`, so the sqlx and libsql
+/// code paths only differ in how they run the query.
+fn upsert_fields(path: &str, bytes: &[u8]) -> UpsertFields {
+    UpsertFields {
+        id: Uuid::new_v4().to_string(),
+        content_type: detect_content_type(path, bytes),
+        size: bytes.len() as i64,
+        now: now_unix(),
+    }
+}
+
+/// Create or overwrite a file against a local SQLite pool. On conflict, the row's
+/// existing `id`/`created_at` are kept rather than the ones computed for this call,
+/// so a file's identity and creation time survive re-uploads.
+pub async fn upsert(pool: &SqlitePool, path: &str, bytes: &[u8]) -> DbResult<()> {
+    debug!("Upserting file at path: {}", path);
+
+    let fields = upsert_fields(path, bytes);
+
+    sqlx::query(
+        "INSERT INTO files (id, path, content, content_type, size, last_modified, created_at) \
+         VALUES (?, ?, ?, ?, ?, ?, ?) \
+         ON CONFLICT(path) DO UPDATE SET \
+             content = excluded.content, \
+             content_type = excluded.content_type, \
+             size = excluded.size, \
+             last_modified = excluded.last_modified",
+    )
+    .bind(fields.id)
+    .bind(path)
+    .bind(bytes)
+    .bind(fields.content_type)
+    .bind(fields.size)
+    .bind(fields.now)
+    .bind(fields.now)
+    .execute(pool)
+    .await
+    .map_err(|e| DbError::Query(format!("Failed to upsert file: {}", e)))?;
+
+    Ok(())
+}
+
+/// Delete a file by path from a local SQLite pool. Returns whether a row was removed.
+pub async fn delete(pool: &SqlitePool, path: &str) -> DbResult<bool> {
+    debug!("Deleting file at path: {}", path);
+
+    let result = sqlx::query("DELETE FROM files WHERE path = ?")
+        .bind(path)
+        .execute(pool)
+        .await
+        .map_err(|e| DbError::Query(format!("Failed to delete file: {}", e)))?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Create or overwrite a file over a raw libsql connection (remote or replica
+/// primary). On conflict, the row's existing `id`/`created_at` are kept rather than
+/// the ones computed for this call, so a file's identity and creation time survive
+/// re-uploads.
+pub async fn upsert_libsql(conn: &libsql::Connection, path: &str, bytes: &[u8]) -> DbResult<()> {
+    let fields = upsert_fields(path, bytes);
+
+    conn.execute(
+        "INSERT INTO files (id, path, content, content_type, size, last_modified, created_at) \
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7) \
+         ON CONFLICT(path) DO UPDATE SET \
+             content = excluded.content, \
+             content_type = excluded.content_type, \
+             size = excluded.size, \
+             last_modified = excluded.last_modified",
+        params![
+            fields.id,
+            path.to_string(),
+            bytes.to_vec(),
+            fields.content_type,
+            fields.size,
+            fields.now,
+            fields.now
+        ],
+    )
+    .await
+    .map_err(|e| DbError::Query(format!("Failed to upsert file: {}", e)))?;
+
+    Ok(())
+}
+
+/// Delete a file by path over a raw libsql connection (remote or replica primary).
+/// Returns whether a row was removed.
+pub async fn delete_libsql(conn: &libsql::Connection, path: &str) -> DbResult<bool> {
+    let affected = conn
+        .execute("DELETE FROM files WHERE path = ?1", params![path.to_string()])
+        .await
+        .map_err(|e| DbError::Query(format!("Failed to delete file: {}", e)))?;
+
+    Ok(affected > 0)
+}
+
+/// Detect a file's content type from its extension, falling back to a magic-byte sniff
+/// and finally to `application/octet-stream`
+fn detect_content_type(path: &str, bytes: &[u8]) -> String {
+    if let Some(content_type) = Path::new(path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .and_then(content_type_for_extension)
+    {
+        return content_type.to_string();
+    }
+
+    sniff_content_type(bytes)
+        .unwrap_or("application/octet-stream")
+        .to_string()
+}
+
+fn content_type_for_extension(ext: &str) -> Option<&'static str> {
+    match ext.to_ascii_lowercase().as_str() {
+        "html" | "htm" => Some("text/html"),
+        "txt" => Some("text/plain"),
+        "css" => Some("text/css"),
+        "js" => Some("application/javascript"),
+        "json" => Some("application/json"),
+        "png" => Some("image/png"),
+        "jpg" | "jpeg" => Some("image/jpeg"),
+        "gif" => Some("image/gif"),
+        "svg" => Some("image/svg+xml"),
+        "pdf" => Some("application/pdf"),
+        "xml" => Some("application/xml"),
+        "wasm" => Some("application/wasm"),
+        _ => None,
+    }
+}
+
+/// Sniff a handful of common magic byte signatures
+fn sniff_content_type(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("image/png")
+    } else if bytes.starts_with(b"\xff\xd8\xff") {
+        Some("image/jpeg")
+    } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        Some("image/gif")
+    } else if bytes.starts_with(b"%PDF-") {
+        Some("application/pdf")
+    } else {
+        None
+    }
+}
+
+fn now_unix() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64
+}
@@ -1,51 +1,189 @@
+use std::collections::HashMap;
+
+use sqlx::Row;
 use tracing::{debug, info};
 
-use crate::db::DbPool;
+use crate::db::connection::{DbBackend, DbExecutor};
 use crate::db::error::{DbError, DbResult};
+use crate::db::DbPool;
+
+/// A single embedded migration: a monotonically increasing version, a short
+/// human-readable name, and the SQL to apply.
+struct Migration {
+    version: i64,
+    name: &'static str,
+    sql: &'static str,
+}
 
-/// Run all necessary migrations to bring the database schema up to date
+/// All migrations, in the order they must be applied. Never edit the SQL of a
+/// migration once it has shipped - add a new one instead, otherwise
+/// `run_migrations` will refuse to start on databases that already applied it.
+const MIGRATIONS: &[Migration] = &[Migration {
+    version: 1,
+    name: "create_files_table",
+    sql: include_str!("migrations/0001_create_files_table.sql"),
+}];
+
+/// Run all pending migrations to bring the database schema up to date.
+///
+/// The local backend gets full version/checksum-tracked migrations via a
+/// `_migrations` bookkeeping table. Remote and replica backends have no separate
+/// provisioning step anywhere in this codebase, so rather than silently skipping
+/// (and leaving a fresh Turso database permanently missing the `files` table) we
+/// apply the same migration SQL directly against the backend. Every migration is
+/// written as `CREATE TABLE/INDEX IF NOT EXISTS`, so re-applying it on each
+/// startup is safe even without drift tracking.
 pub async fn run_migrations(pool: &DbPool) -> DbResult<()> {
+    let DbBackend::Local(pool) = pool else {
+        return run_remote_migrations(pool).await;
+    };
+
     info!("Running database migrations");
 
-    let mut db = pool.acquire().await.map_err(|e| {
+    let mut conn = pool.acquire().await.map_err(|e| {
         DbError::Connection(format!("Failed to acquire database connection: {}", e))
     })?;
 
-    // Create files table if it doesn't exist
-    debug!("Creating files table if it doesn't exist");
-    create_files_table(&mut db).await?;
+    ensure_migrations_table(&mut conn).await?;
+    let applied = applied_migrations(&mut conn).await?;
+
+    for migration in MIGRATIONS {
+        match applied.get(&migration.version) {
+            Some(existing_checksum) => {
+                let expected_checksum = checksum(migration.sql);
+                if existing_checksum != &expected_checksum {
+                    return Err(DbError::Initialization(format!(
+                        "Checksum mismatch for migration {} ({}): the applied SQL no longer \
+                         matches what's embedded in the binary",
+                        migration.version, migration.name
+                    )));
+                }
+                debug!(
+                    "Migration {} ({}) already applied",
+                    migration.version, migration.name
+                );
+            }
+            None => {
+                info!("Applying migration {} ({})", migration.version, migration.name);
+                apply_migration(&mut conn, migration).await?;
+            }
+        }
+    }
 
     info!("Database migrations completed successfully");
     Ok(())
 }
 
-/// Create the files table if it doesn't exist
-async fn create_files_table(conn: &mut sqlx::SqliteConnection) -> DbResult<()> {
-    let create_table_sql = r#"
-    CREATE TABLE IF NOT EXISTS files (
-        id TEXT PRIMARY KEY,
-        path TEXT NOT NULL UNIQUE,
-        content BLOB NOT NULL,
-        content_type TEXT NOT NULL,
-        size INTEGER NOT NULL,
-        last_modified INTEGER NOT NULL,
-        created_at INTEGER NOT NULL
-    );
-    CREATE INDEX IF NOT EXISTS idx_files_path ON files(path);
-    "#;
+/// Apply migrations against a remote or replica backend. There's no `_migrations`
+/// bookkeeping here - each migration must be idempotent on its own - but this is
+/// what actually provisions the schema on a fresh Turso database, instead of
+/// deferring to a provisioning process that doesn't exist.
+async fn run_remote_migrations(pool: &DbPool) -> DbResult<()> {
+    info!("Applying migrations directly against remote/replica backend (no checksum tracking)");
+
+    for migration in MIGRATIONS {
+        pool.execute(migration.sql).await.map_err(|e| {
+            DbError::Initialization(format!(
+                "Failed to apply migration {} ({}) to remote backend: {}",
+                migration.version, migration.name, e
+            ))
+        })?;
+    }
+
+    info!("Database migrations completed successfully");
+    Ok(())
+}
 
-    sqlx::query(create_table_sql)
-        .execute(conn)
+/// Create the `_migrations` bookkeeping table if it doesn't exist
+async fn ensure_migrations_table(conn: &mut sqlx::SqliteConnection) -> DbResult<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS _migrations (
+            version INTEGER PRIMARY KEY,
+            name TEXT NOT NULL,
+            applied_at INTEGER NOT NULL,
+            checksum TEXT NOT NULL
+        );
+        "#,
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| DbError::Query(format!("Failed to create _migrations table: {}", e)))?;
+
+    Ok(())
+}
+
+/// Load the set of already-applied migrations, keyed by version
+async fn applied_migrations(conn: &mut sqlx::SqliteConnection) -> DbResult<HashMap<i64, String>> {
+    let rows = sqlx::query("SELECT version, checksum FROM _migrations")
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(|e| DbError::Query(format!("Failed to read applied migrations: {}", e)))?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| (row.get("version"), row.get("checksum")))
+        .collect())
+}
+
+/// Apply a single migration and record it in `_migrations`, all within one transaction
+async fn apply_migration(conn: &mut sqlx::SqliteConnection, migration: &Migration) -> DbResult<()> {
+    let mut tx = sqlx::Connection::begin(conn)
+        .await
+        .map_err(|e| DbError::Query(format!("Failed to start migration transaction: {}", e)))?;
+
+    sqlx::query(migration.sql)
+        .execute(&mut *tx)
         .await
-        .map_err(|e| DbError::Query(format!("Failed to create files table: {}", e)))?;
+        .map_err(|e| {
+            DbError::Query(format!(
+                "Failed to apply migration {} ({}): {}",
+                migration.version, migration.name, e
+            ))
+        })?;
+
+    sqlx::query(
+        "INSERT INTO _migrations (version, name, applied_at, checksum) VALUES (?, ?, strftime('%s', 'now'), ?)",
+    )
+    .bind(migration.version)
+    .bind(migration.name)
+    .bind(checksum(migration.sql))
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| DbError::Query(format!("Failed to record applied migration: {}", e)))?;
+
+    tx.commit()
+        .await
+        .map_err(|e| DbError::Query(format!("Failed to commit migration transaction: {}", e)))?;
 
-    debug!("Files table created or already exists");
     Ok(())
 }
 
-/// Add a test file to the database (for development purposes)
+/// A cheap, stable checksum used to detect when an already-applied migration's SQL
+/// has drifted from what shipped in the binary. Not cryptographic - just needs to
+/// change whenever the SQL does.
+fn checksum(sql: &str) -> String {
+    const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+    const FNV_PRIME: u64 = 0x100000001b3;
+
+    let mut hash = FNV_OFFSET;
+    for byte in sql.as_bytes() {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+
+    format!("{:016x}", hash)
+}
+
+/// Add a test file to the database (for development purposes, local backend only)
 #[allow(dead_code)]
 pub async fn add_test_file(pool: &DbPool) -> DbResult<()> {
+    let DbBackend::Local(pool) = pool else {
+        return Err(DbError::Configuration(
+            "add_test_file is only supported against a local database".into(),
+        ));
+    };
+
     debug!("Adding test file to database");
 
     // Create a sample test file
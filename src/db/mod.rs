@@ -1,5 +1,6 @@
 pub mod connection;
 pub mod error;
+pub mod files;
 pub mod migrations;
 
 // Test modules
@@ -7,13 +8,18 @@ pub mod migrations;
 mod connection_tests;
 #[cfg(test)]
 mod error_tests;
+#[cfg(test)]
+pub mod test_support;
 
-pub use connection::DbPool;
+pub use connection::{DbExecutor, DbPool};
 pub use error::{DbError, DbResult};
+pub use files::FileRecord;
+#[cfg(test)]
+pub use test_support::{spawn_test_db, TestDbGuard};
 
-/// Initialize the database connection pool
+/// Initialize the database, dispatching to the right storage backend
 pub async fn init() -> DbResult<DbPool> {
-    connection::create_pool().await
+    connection::initialize_database().await
 }
 
 /// Run database migrations
@@ -29,5 +35,20 @@ pub async fn add_test_file(pool: &DbPool) -> DbResult<()> {
 
 /// Health check for the database connection
 pub async fn health_check(pool: &DbPool) -> DbResult<()> {
-    connection::check_connection(pool).await
+    pool.health_check().await
+}
+
+/// Look up a file by its path
+pub async fn get_file(pool: &DbPool, path: &str) -> DbResult<Option<FileRecord>> {
+    pool.fetch_file_by_path(path).await
+}
+
+/// Create or overwrite a file
+pub async fn upsert_file(pool: &DbPool, path: &str, bytes: &[u8]) -> DbResult<()> {
+    pool.upsert_file(path, bytes).await
+}
+
+/// Delete a file by path. Returns whether a row was removed.
+pub async fn delete_file(pool: &DbPool, path: &str) -> DbResult<bool> {
+    pool.delete_file(path).await
 }
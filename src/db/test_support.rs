@@ -0,0 +1,56 @@
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+
+use crate::db::connection::DbBackend;
+use crate::db::{DbPool, run_migrations};
+
+/// Deletes an ephemeral test database - and its WAL/SHM/journal sidecar files - when
+/// dropped. Keep this alive for as long as the pool returned alongside it is in use.
+pub struct TestDbGuard {
+    path: PathBuf,
+}
+
+impl Drop for TestDbGuard {
+    fn drop(&mut self) {
+        for suffix in ["", "-wal", "-shm", "-journal"] {
+            let _ = std::fs::remove_file(format!("{}{}", self.path.display(), suffix));
+        }
+    }
+}
+
+/// Spin up an isolated, uniquely-named SQLite database and run the real migrations
+/// against it, so integration tests exercise the actual migration path rather than a
+/// hand-built schema. Returns the pool alongside a `TestDbGuard` that cleans up the
+/// database file on drop - hold onto it for the lifetime of the test.
+pub async fn spawn_test_db() -> (DbPool, TestDbGuard) {
+    let path = tempfile::Builder::new()
+        .prefix("turserver-test-")
+        .suffix(".db")
+        .tempfile()
+        .expect("failed to create temp file for test database")
+        .into_temp_path()
+        .keep()
+        .expect("failed to hand off temp path to TestDbGuard");
+
+    let url = format!("sqlite://{}", path.display());
+
+    let connect_options = SqliteConnectOptions::from_str(&url)
+        .expect("test database URL is always valid")
+        .create_if_missing(true);
+
+    let pool = SqlitePoolOptions::new()
+        .max_connections(5)
+        .connect_with(connect_options)
+        .await
+        .expect("failed to create test database");
+
+    let pool = DbBackend::Local(pool);
+
+    run_migrations(&pool)
+        .await
+        .expect("failed to run migrations against test database");
+
+    (pool, TestDbGuard { path })
+}
@@ -1,13 +1,14 @@
 use axum::{
     Router,
+    body::Bytes,
     extract::{Path, State},
-    http::StatusCode,
+    http::{HeaderValue, StatusCode, header},
     response::{IntoResponse, Response},
-    routing::get,
+    routing::{get, post},
 };
 use std::net::SocketAddr;
 use tower_http::trace::TraceLayer;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 // Import our db module
@@ -73,15 +74,29 @@ async fn main() -> anyhow::Result<()> {
         anyhow::anyhow!("Database migration error: {}", e)
     })?;
 
+    // If we have an embedded replica, keep it synced in the background
+    if let db::DbPool::Replica(replica) = &db_pool {
+        let replica = replica.clone();
+        let interval = db::connection::replica_sync_interval();
+        info!("Starting replica sync loop every {:?}", interval);
+
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                match replica.sync().await {
+                    Ok(frame_no) => info!("Replica synced, last frame: {}", frame_no),
+                    Err(e) => warn!("Replica sync failed: {}", e),
+                }
+            }
+        });
+    }
+
     // Create application state
     let state = AppState { db_pool };
 
     // Create router with routes
-    let app = Router::new()
-        .route("/", get(root_handler))
-        .route("/{path}", get(path_handler))
-        .layer(TraceLayer::new_for_http())
-        .with_state(state);
+    let app = build_router(state);
 
     // Set up the server address
     let addr = SocketAddr::from(([127, 0, 0, 1], 3000));
@@ -97,22 +112,207 @@ async fn main() -> anyhow::Result<()> {
     Ok(())
 }
 
+// Build the axum router, shared between `main` and the test harness
+fn build_router(state: AppState) -> Router {
+    Router::new()
+        .route("/", get(root_handler))
+        .route(
+            "/{path}",
+            get(path_handler).put(put_handler).delete(delete_handler),
+        )
+        .route("/health", get(health_handler))
+        .route("/admin/sync", post(admin_sync_handler))
+        .layer(TraceLayer::new_for_http())
+        .with_state(state)
+}
+
 // Handler for the root path
 async fn root_handler() -> &'static str {
     "Turserver - File server powered by Turso"
 }
 
-// Handler for other paths - will be replaced with database lookup logic later
-async fn path_handler(State(state): State<AppState>, Path(path): Path<String>) -> Result<String> {
+// Handler for other paths - looks the file up in the `files` table and serves its contents
+async fn path_handler(State(state): State<AppState>, Path(path): Path<String>) -> Result<Response> {
     info!("Request for path: {}", path);
 
-    // Perform health check to ensure the database is working
-    db::health_check(&state.db_pool).await?;
+    let record = db::get_file(&state.db_pool, &path)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Path '{}' not found", path)))?;
+
+    let last_modified = std::time::UNIX_EPOCH
+        + std::time::Duration::from_secs(record.last_modified.max(0) as u64);
+    let etag = format!("\"{}-{}\"", record.last_modified, record.size);
 
-    // This is a placeholder - will be replaced with DB lookup later
-    if path == "test" {
-        Ok("This is a test file".to_string())
+    let mut response = record.content.into_response();
+    let headers = response.headers_mut();
+    headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_str(&record.content_type)
+            .unwrap_or_else(|_| HeaderValue::from_static("application/octet-stream")),
+    );
+    headers.insert(header::CONTENT_LENGTH, HeaderValue::from(record.size as u64));
+    headers.insert(
+        header::LAST_MODIFIED,
+        HeaderValue::from_str(&httpdate::fmt_http_date(last_modified))
+            .expect("HTTP date is always valid header value"),
+    );
+    headers.insert(
+        header::ETAG,
+        HeaderValue::from_str(&etag).expect("ETag is always valid header value"),
+    );
+
+    Ok(response)
+}
+
+// Handler for uploading (or overwriting) a file at a path
+async fn put_handler(
+    State(state): State<AppState>,
+    Path(path): Path<String>,
+    body: Bytes,
+) -> Result<StatusCode> {
+    info!("Upload for path: {}", path);
+
+    db::upsert_file(&state.db_pool, &path, &body).await?;
+
+    Ok(StatusCode::CREATED)
+}
+
+// Handler for deleting a file at a path
+async fn delete_handler(State(state): State<AppState>, Path(path): Path<String>) -> Result<StatusCode> {
+    info!("Delete for path: {}", path);
+
+    if db::delete_file(&state.db_pool, &path).await? {
+        Ok(StatusCode::NO_CONTENT)
     } else {
         Err(AppError::NotFound(format!("Path '{}' not found", path)))
     }
 }
+
+// Handler that confirms the database backend is reachable, reporting how far an
+// embedded replica has synced
+async fn health_handler(State(state): State<AppState>) -> Result<String> {
+    db::health_check(&state.db_pool).await?;
+
+    let status = match &state.db_pool {
+        db::DbPool::Replica(replica) => format!(
+            "OK (embedded replica, last synced frame: {})",
+            replica.last_synced_frame()
+        ),
+        _ => "OK".to_string(),
+    };
+
+    Ok(status)
+}
+
+// Handler that forces an immediate replica sync and reports how far it caught up
+async fn admin_sync_handler(State(state): State<AppState>) -> Result<String> {
+    let db::DbPool::Replica(replica) = &state.db_pool else {
+        return Ok("No embedded replica configured; nothing to sync".to_string());
+    };
+
+    let frame_no = replica.sync().await?;
+    Ok(format!("Replica synced, last applied frame: {}", frame_no))
+}
+
+/// Build a full router wired to a fresh, isolated test database - for exercising
+/// handler routes end to end. The returned guard deletes the test database file
+/// when dropped, so keep it alive for the lifetime of the test.
+#[cfg(test)]
+async fn spawn_test_app() -> (Router, db::TestDbGuard) {
+    let (db_pool, guard) = db::spawn_test_db().await;
+    (build_router(AppState { db_pool }), guard)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn test_path_handler_not_found() {
+        let (app, _guard) = spawn_test_app().await;
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/missing.txt")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_put_then_get_roundtrip() {
+        let (app, _guard) = spawn_test_app().await;
+
+        let put_response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("PUT")
+                    .uri("/hello.txt")
+                    .body(Body::from("hello world"))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(put_response.status(), StatusCode::CREATED);
+
+        let get_response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/hello.txt")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(get_response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_put_then_delete() {
+        let (app, _guard) = spawn_test_app().await;
+
+        app.clone()
+            .oneshot(
+                Request::builder()
+                    .method("PUT")
+                    .uri("/bye.txt")
+                    .body(Body::from("goodbye"))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let delete_response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("DELETE")
+                    .uri("/bye.txt")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(delete_response.status(), StatusCode::NO_CONTENT);
+
+        let get_response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/bye.txt")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(get_response.status(), StatusCode::NOT_FOUND);
+    }
+}